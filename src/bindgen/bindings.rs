@@ -0,0 +1,34 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+use bindgen::config::Config;
+use bindgen::ir::{Constant, Function, ItemContainer, Static};
+
+/// The result of `Library::generate`: everything needed to write out a
+/// header, already ordered and renamed.
+pub struct Bindings {
+    pub config: Config,
+    pub constants: Vec<Constant>,
+    pub globals: Vec<Static>,
+    pub items: Vec<ItemContainer>,
+    pub functions: Vec<Function>,
+}
+
+impl Bindings {
+    pub fn new(
+        config: Config,
+        constants: Vec<Constant>,
+        globals: Vec<Static>,
+        items: Vec<ItemContainer>,
+        functions: Vec<Function>,
+    ) -> Bindings {
+        Bindings {
+            config: config,
+            constants: constants,
+            globals: globals,
+            items: items,
+            functions: functions,
+        }
+    }
+}
@@ -0,0 +1,18 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+/// A parsed `#[cfg(...)]` predicate tree, as found on an item, preserved so
+/// it can be re-emitted as an `#if` guard (or, when it's statically
+/// resolvable, pruned -- see `Library::resolve_cfg`).
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Cfg {
+    /// A bare identifier, e.g. `cfg(test)` or `cfg(unix)`.
+    Boolean(String),
+    /// A `key = "value"` leaf, e.g. `cfg(feature = "foo")` or
+    /// `cfg(target_os = "linux")`.
+    Named(String, String),
+    All(Vec<Cfg>),
+    Any(Vec<Cfg>),
+    Not(Box<Cfg>),
+}
@@ -0,0 +1,92 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+use std::collections::HashMap;
+
+use bindgen::ir::Path;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+pub enum Language {
+    Cxx,
+    C,
+}
+
+impl Default for Language {
+    fn default() -> Language {
+        Language::Cxx
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+pub enum Style {
+    Both,
+    Tag,
+    Type,
+}
+
+impl Style {
+    pub fn generate_typedef(&self) -> bool {
+        *self != Style::Tag
+    }
+}
+
+impl Default for Style {
+    fn default() -> Style {
+        Style::Both
+    }
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct ExportConfig {
+    pub include: Vec<Path>,
+    pub exclude: Vec<String>,
+    pub rename: HashMap<String, String>,
+}
+
+impl ExportConfig {
+    pub fn rename(&self, path: &mut Path) {
+        if let Some(renamed) = self.rename.get(path.name()) {
+            *path = Path::new(renamed.clone());
+        }
+    }
+}
+
+/// Resolves `#[cfg(...)]` predicates against the build configuration the
+/// user tells us they're generating for, so items gated on it can be
+/// pruned outright instead of wrapped in an `#if` guard. See
+/// `Library::resolve_cfg`/`eval_cfg` for how this is consumed.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct CfgConfig {
+    /// Cargo features that are considered active, matched against
+    /// `#[cfg(feature = "...")]`.
+    pub active_features: Vec<String>,
+    /// Whether `#[cfg(test)]` items should be treated as active.
+    pub test: bool,
+    /// Arbitrary `key = "value"` pairs, e.g. `target_os = "linux"`,
+    /// matched against `#[cfg(key = "value")]`.
+    #[serde(flatten)]
+    pub defines: HashMap<String, String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    pub language: Language,
+    pub style: Style,
+    pub export: ExportConfig,
+    pub cfg: CfgConfig,
+}
+
+impl Default for Config {
+    fn default() -> Config {
+        Config {
+            language: Language::default(),
+            style: Style::default(),
+            export: ExportConfig::default(),
+            cfg: CfgConfig::default(),
+        }
+    }
+}
@@ -0,0 +1,16 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+/// The resolver passed to each item's `populate_ctyperesolver`/`set_ctype`
+/// in `Library::set_ctype`'s C-only pass. It holds no state: it does not
+/// record which exported names need a `struct`/`union`/`enum` tag prefix
+/// versus a bare typedef'd name.
+#[derive(Debug, Default)]
+pub struct CTypeResolver;
+
+impl CTypeResolver {
+    pub fn new() -> CTypeResolver {
+        CTypeResolver::default()
+    }
+}
@@ -0,0 +1,28 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+use std::collections::HashSet;
+
+use bindgen::ir::{ItemContainer, Path};
+
+#[derive(Default)]
+pub struct Dependencies {
+    pub items: HashSet<Path>,
+    pub order: Vec<ItemContainer>,
+}
+
+impl Dependencies {
+    pub fn new() -> Dependencies {
+        Dependencies::default()
+    }
+
+    /// `order` is built up in dependency-respecting order already, by each
+    /// `add_dependencies` call pushing an item only after recursing into
+    /// what it references -- so all that's left is to drop the duplicate
+    /// pushes that come from two items sharing a sub-dependency.
+    pub fn sort(&mut self) {
+        let mut seen = HashSet::new();
+        self.order.retain(|item| seen.insert(item.deref().name().to_owned()));
+    }
+}
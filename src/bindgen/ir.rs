@@ -0,0 +1,797 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+use std::cell::{Ref, RefCell};
+use std::collections::HashMap;
+use std::fmt;
+use std::rc::Rc;
+
+use bindgen::cfg::Cfg;
+use bindgen::config::Config;
+use bindgen::ctyperesolver::CTypeResolver;
+use bindgen::dependencies::Dependencies;
+use bindgen::library::Library;
+use bindgen::monomorph::Monomorphs;
+use bindgen::traits::Trait;
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Deserialize)]
+#[serde(transparent)]
+pub struct Path(String);
+
+impl Path {
+    pub fn new<S: Into<String>>(name: S) -> Path {
+        Path(name.into())
+    }
+
+    pub fn name(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Display for Path {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct AnnotationSet {
+    map: HashMap<String, String>,
+}
+
+impl AnnotationSet {
+    pub fn new() -> AnnotationSet {
+        AnnotationSet { map: HashMap::new() }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Documentation {
+    pub doc_comment: Vec<String>,
+}
+
+impl Documentation {
+    pub fn none() -> Documentation {
+        Documentation::default()
+    }
+}
+
+/// A lowered C type. Anonymous tuples get their own variant rather than
+/// being modeled as a `Path`, since (unlike `Result<T, E>`) they have no
+/// name of their own until `instantiate_monomorphs` mangles one for them.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Type {
+    Primitive(String),
+    Path(Path, Vec<Type>),
+    Tuple(Vec<Type>),
+    Ptr(Box<Type>, bool),
+    ConstArray(Box<Type>, u64),
+    FuncPtr(Box<Type>, Vec<Type>),
+}
+
+impl Type {
+    pub fn to_c_string(&self) -> String {
+        match *self {
+            Type::Primitive(ref s) => s.clone(),
+            Type::Path(ref path, ..) => path.name().to_owned(),
+            Type::Tuple(..) => {
+                // Reached by the unit type `()`, which is never
+                // monomorphized, and by any tuple that slipped through
+                // without being monomorphized into a named struct first.
+                "void".to_owned()
+            }
+            Type::Ptr(ref t, is_const) => {
+                format!("{}{}*", t.to_c_string(), if is_const { " const " } else { " " })
+            }
+            Type::ConstArray(ref t, len) => format!("{}[{}]", t.to_c_string(), len),
+            Type::FuncPtr(ref ret, ref args) => {
+                let args = args.iter().map(Type::to_c_string).collect::<Vec<_>>().join(", ");
+                format!("{} (*)({})", ret.to_c_string(), args)
+            }
+        }
+    }
+
+    pub fn add_dependencies(&self, library: &Library, out: &mut Dependencies) {
+        match *self {
+            Type::Path(ref path, ref generics) => {
+                for generic in generics {
+                    generic.add_dependencies(library, out);
+                }
+                if !out.items.contains(path) {
+                    if let Some(items) = library.get_items(path) {
+                        out.items.insert(path.clone());
+                        for item in &items {
+                            item.deref().add_dependencies(library, out);
+                        }
+                        for item in items {
+                            out.order.push(item);
+                        }
+                    }
+                }
+            }
+            Type::Tuple(ref items) => {
+                for item in items {
+                    item.add_dependencies(library, out);
+                }
+            }
+            Type::Ptr(ref t, _) | Type::ConstArray(ref t, _) => t.add_dependencies(library, out),
+            Type::FuncPtr(ref ret, ref args) => {
+                ret.add_dependencies(library, out);
+                for arg in args {
+                    arg.add_dependencies(library, out);
+                }
+            }
+            Type::Primitive(_) => {}
+        }
+    }
+
+    pub fn add_monomorphs(&self, library: &Library, out: &mut Monomorphs) {
+        match *self {
+            Type::Path(ref path, ref generics) => {
+                for generic in generics {
+                    generic.add_monomorphs(library, out);
+                }
+                if !generics.is_empty() {
+                    out.add_path_monomorph(path, generics, library);
+                }
+            }
+            Type::Tuple(ref items) => {
+                for item in items {
+                    item.add_monomorphs(library, out);
+                }
+                // The unit type `()` lowers to `void` (see `to_c_string`
+                // above) rather than a synthesized empty struct, so there's
+                // nothing to monomorphize for it.
+                if !items.is_empty() {
+                    out.add_tuple_monomorph(items);
+                }
+            }
+            Type::Ptr(ref t, _) | Type::ConstArray(ref t, _) => t.add_monomorphs(library, out),
+            Type::FuncPtr(ref ret, ref args) => {
+                ret.add_monomorphs(library, out);
+                for arg in args {
+                    arg.add_monomorphs(library, out);
+                }
+            }
+            Type::Primitive(_) => {}
+        }
+    }
+
+    pub fn mangle_paths(&mut self, monomorphs: &Monomorphs) {
+        match *self {
+            Type::Path(ref mut path, ref mut generics) => {
+                // The lookup key recorded by `add_path_monomorph` is keyed on
+                // the *original* (pre-mangling) generics, so the lookup has
+                // to happen before the recursive call below mutates them --
+                // otherwise a nested instantiation like `Vec<Foo<Bar>>`
+                // mangles its inner `Foo<Bar>` first and then fails to find
+                // `Vec_Foo_Bar` under the now-rewritten generics.
+                let mangled = if !generics.is_empty() {
+                    monomorphs.mangled_path(path, generics)
+                } else {
+                    None
+                };
+                for generic in generics.iter_mut() {
+                    generic.mangle_paths(monomorphs);
+                }
+                if let Some(mangled) = mangled {
+                    *path = mangled;
+                    generics.clear();
+                }
+            }
+            Type::Tuple(ref mut items) => {
+                // Same ordering constraint as the `Path` arm above: the
+                // dedup key is the original, pre-mangling list of element
+                // types, so it has to be looked up before the recursive
+                // call below mangles those elements in place.
+                let mangled = monomorphs.mangled_tuple_path(items);
+                for item in items.iter_mut() {
+                    item.mangle_paths(monomorphs);
+                }
+                if let Some(mangled) = mangled {
+                    *self = Type::Path(mangled, Vec::new());
+                }
+            }
+            Type::Ptr(ref mut t, _) | Type::ConstArray(ref mut t, _) => t.mangle_paths(monomorphs),
+            Type::FuncPtr(ref mut ret, ref mut args) => {
+                ret.mangle_paths(monomorphs);
+                for arg in args.iter_mut() {
+                    arg.mangle_paths(monomorphs);
+                }
+            }
+            Type::Primitive(_) => {}
+        }
+    }
+}
+
+/// Shared behavior needed to resolve an item referenced by `Path` back to
+/// its definition, regardless of what kind of item it is -- used by
+/// `Dependencies` and by `Library::get_items`'s `config.export.include`
+/// resolution.
+pub trait Item {
+    fn name(&self) -> &str;
+    fn add_dependencies(&self, library: &Library, out: &mut Dependencies);
+}
+
+#[derive(Clone)]
+pub enum ItemContainer {
+    Constant(Rc<RefCell<Constant>>),
+    Static(Rc<RefCell<Static>>),
+    Enum(Rc<RefCell<Enum>>),
+    Struct(Rc<RefCell<Struct>>),
+    Union(Rc<RefCell<Union>>),
+    OpaqueItem(Rc<RefCell<OpaqueItem>>),
+    Typedef(Rc<RefCell<Typedef>>),
+    Trait(Rc<RefCell<Trait>>),
+}
+
+impl ItemContainer {
+    pub fn deref(&self) -> Ref<Item> {
+        match *self {
+            ItemContainer::Constant(ref x) => Ref::map(x.borrow(), |x| x as &Item),
+            ItemContainer::Static(ref x) => Ref::map(x.borrow(), |x| x as &Item),
+            ItemContainer::Enum(ref x) => Ref::map(x.borrow(), |x| x as &Item),
+            ItemContainer::Struct(ref x) => Ref::map(x.borrow(), |x| x as &Item),
+            ItemContainer::Union(ref x) => Ref::map(x.borrow(), |x| x as &Item),
+            ItemContainer::OpaqueItem(ref x) => Ref::map(x.borrow(), |x| x as &Item),
+            ItemContainer::Typedef(ref x) => Ref::map(x.borrow(), |x| x as &Item),
+            ItemContainer::Trait(ref x) => Ref::map(x.borrow(), |x| x as &Item),
+        }
+    }
+}
+
+pub trait ToItemContainer {
+    fn to_container(rc: Rc<RefCell<Self>>) -> ItemContainer;
+}
+
+macro_rules! impl_to_item_container {
+    ($ty:ident) => {
+        impl ToItemContainer for $ty {
+            fn to_container(rc: Rc<RefCell<$ty>>) -> ItemContainer {
+                ItemContainer::$ty(rc)
+            }
+        }
+    };
+}
+
+impl_to_item_container!(Enum);
+impl_to_item_container!(Struct);
+impl_to_item_container!(Union);
+impl_to_item_container!(OpaqueItem);
+impl_to_item_container!(Typedef);
+
+#[derive(Clone)]
+pub struct ItemMap<T> {
+    items: HashMap<String, Vec<Rc<RefCell<T>>>>,
+}
+
+impl<T> Default for ItemMap<T> {
+    fn default() -> ItemMap<T> {
+        ItemMap { items: HashMap::new() }
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for ItemMap<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_map().entries(self.items.iter()).finish()
+    }
+}
+
+impl<T: Clone> ItemMap<T> {
+    pub fn new() -> ItemMap<T> {
+        ItemMap::default()
+    }
+
+    pub fn to_vec(&self) -> Vec<T> {
+        let mut out = Vec::new();
+        for bucket in self.items.values() {
+            for item in bucket {
+                out.push(item.borrow().clone());
+            }
+        }
+        out
+    }
+
+    /// Looks up a still-generic definition by name, for `Monomorphs` to
+    /// clone and specialize when it finds an instantiation of it.
+    pub fn get_cloned(&self, name: &str) -> Option<T> {
+        self.items
+            .get(name)
+            .and_then(|bucket| bucket.first())
+            .map(|item| item.borrow().clone())
+    }
+}
+
+impl<T> ItemMap<T> {
+    pub fn try_insert_with_name(&mut self, name: String, item: T) {
+        self.items
+            .entry(name)
+            .or_insert_with(Vec::new)
+            .push(Rc::new(RefCell::new(item)));
+    }
+
+    pub fn filter<F: Fn(&T) -> bool>(&mut self, f: F) {
+        for bucket in self.items.values_mut() {
+            bucket.retain(|item| !f(&item.borrow()));
+        }
+        self.items.retain(|_, bucket| !bucket.is_empty());
+    }
+
+    pub fn for_all_items<F: FnMut(&T)>(&self, mut f: F) {
+        for bucket in self.items.values() {
+            for item in bucket {
+                f(&item.borrow());
+            }
+        }
+    }
+
+    pub fn for_all_items_mut<F: FnMut(&mut T)>(&mut self, mut f: F) {
+        for bucket in self.items.values_mut() {
+            for item in bucket {
+                f(&mut item.borrow_mut());
+            }
+        }
+    }
+
+    pub fn for_items_mut<F: FnMut(&mut T)>(&mut self, path: &Path, mut f: F) {
+        if let Some(bucket) = self.items.get_mut(path.name()) {
+            for item in bucket {
+                f(&mut item.borrow_mut());
+            }
+        }
+    }
+
+    pub fn rebuild(&mut self)
+    where
+        T: HasPath,
+    {
+        let mut rebuilt = HashMap::new();
+        for bucket in self.items.values() {
+            for item in bucket {
+                let name = item.borrow().path_name().to_owned();
+                rebuilt.entry(name).or_insert_with(Vec::new).push(item.clone());
+            }
+        }
+        self.items = rebuilt;
+    }
+}
+
+impl<T: HasPath> ItemMap<T> {
+    pub fn try_insert(&mut self, item: T) {
+        let name = item.path_name().to_owned();
+        self.try_insert_with_name(name, item);
+    }
+}
+
+impl<T: ToItemContainer> ItemMap<T> {
+    pub fn get_items(&self, p: &Path) -> Option<Vec<ItemContainer>> {
+        self.items
+            .get(p.name())
+            .map(|bucket| bucket.iter().cloned().map(T::to_container).collect())
+    }
+}
+
+/// Items that key an `ItemMap` by their own (possibly just-renamed or
+/// just-mangled) name.
+pub trait HasPath {
+    fn path_name(&self) -> &str;
+}
+
+macro_rules! simple_item {
+    ($ty:ident) => {
+        impl HasPath for $ty {
+            fn path_name(&self) -> &str {
+                &self.name
+            }
+        }
+
+        impl Item for $ty {
+            fn name(&self) -> &str {
+                &self.name
+            }
+
+            fn add_dependencies(&self, _library: &Library, _out: &mut Dependencies) {}
+        }
+    };
+}
+
+#[derive(Debug, Clone)]
+pub struct Constant {
+    pub name: String,
+    pub ty: Type,
+    pub value: String,
+    pub cfg: Option<Cfg>,
+    pub annotations: AnnotationSet,
+    pub documentation: Documentation,
+}
+
+impl Constant {
+    pub fn rename_for_config(&mut self, config: &Config) {
+        if let Some(renamed) = config.export.rename.get(&self.name) {
+            self.name = renamed.clone();
+        }
+    }
+}
+
+impl HasPath for Constant {
+    fn path_name(&self) -> &str {
+        &self.name
+    }
+}
+
+impl Item for Constant {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn add_dependencies(&self, library: &Library, out: &mut Dependencies) {
+        self.ty.add_dependencies(library, out);
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Static {
+    pub name: String,
+    pub ty: Type,
+    pub cfg: Option<Cfg>,
+    pub annotations: AnnotationSet,
+    pub documentation: Documentation,
+}
+
+impl Static {
+    pub fn rename_for_config(&mut self, config: &Config) {
+        if let Some(renamed) = config.export.rename.get(&self.name) {
+            self.name = renamed.clone();
+        }
+    }
+
+    pub fn set_ctype(&mut self, _resolver: &CTypeResolver) {}
+
+    pub fn simplify_option_to_ptr(&mut self) {}
+}
+
+impl HasPath for Static {
+    fn path_name(&self) -> &str {
+        &self.name
+    }
+}
+
+impl Item for Static {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn add_dependencies(&self, library: &Library, out: &mut Dependencies) {
+        self.ty.add_dependencies(library, out);
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Enum {
+    pub name: String,
+    pub variants: Vec<(String, Option<i64>)>,
+    pub cfg: Option<Cfg>,
+    pub annotations: AnnotationSet,
+    pub documentation: Documentation,
+}
+
+impl Enum {
+    pub fn rename_for_config(&mut self, config: &Config) {
+        if let Some(renamed) = config.export.rename.get(&self.name) {
+            self.name = renamed.clone();
+        }
+    }
+
+    pub fn populate_ctyperesolver(&self, _resolver: &mut CTypeResolver) {}
+
+    pub fn set_ctype(&mut self, _resolver: &CTypeResolver) {}
+
+    pub fn annotations(&self) -> &AnnotationSet {
+        &self.annotations
+    }
+
+    pub fn annotations_mut(&mut self) -> &mut AnnotationSet {
+        &mut self.annotations
+    }
+}
+
+simple_item!(Enum);
+
+#[derive(Debug, Clone)]
+pub struct Struct {
+    pub name: String,
+    pub generic_params: Vec<String>,
+    pub fields: Vec<(String, Type, Documentation)>,
+    pub cfg: Option<Cfg>,
+    pub annotations: AnnotationSet,
+    pub documentation: Documentation,
+}
+
+impl Struct {
+    pub fn rename_for_config(&mut self, config: &Config) {
+        if let Some(renamed) = config.export.rename.get(&self.name) {
+            self.name = renamed.clone();
+        }
+    }
+
+    pub fn populate_ctyperesolver(&self, _resolver: &mut CTypeResolver) {}
+
+    pub fn set_ctype(&mut self, _resolver: &CTypeResolver) {}
+
+    pub fn simplify_option_to_ptr(&mut self) {}
+
+    pub fn add_monomorphs(&self, library: &Library, out: &mut Monomorphs) {
+        if !self.generic_params.is_empty() {
+            return;
+        }
+        for &(_, ref ty, _) in &self.fields {
+            ty.add_monomorphs(library, out);
+        }
+    }
+
+    pub fn mangle_paths(&mut self, monomorphs: &Monomorphs) {
+        for &mut (_, ref mut ty, _) in &mut self.fields {
+            ty.mangle_paths(monomorphs);
+        }
+    }
+
+    pub fn annotations(&self) -> &AnnotationSet {
+        &self.annotations
+    }
+
+    pub fn annotations_mut(&mut self) -> &mut AnnotationSet {
+        &mut self.annotations
+    }
+}
+
+impl HasPath for Struct {
+    fn path_name(&self) -> &str {
+        &self.name
+    }
+}
+
+impl Item for Struct {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn add_dependencies(&self, library: &Library, out: &mut Dependencies) {
+        for &(_, ref ty, _) in &self.fields {
+            ty.add_dependencies(library, out);
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Union {
+    pub name: String,
+    pub generic_params: Vec<String>,
+    pub fields: Vec<(String, Type, Documentation)>,
+    pub cfg: Option<Cfg>,
+    pub annotations: AnnotationSet,
+    pub documentation: Documentation,
+}
+
+impl Union {
+    pub fn rename_for_config(&mut self, config: &Config) {
+        if let Some(renamed) = config.export.rename.get(&self.name) {
+            self.name = renamed.clone();
+        }
+    }
+
+    pub fn populate_ctyperesolver(&self, _resolver: &mut CTypeResolver) {}
+
+    pub fn set_ctype(&mut self, _resolver: &CTypeResolver) {}
+
+    pub fn simplify_option_to_ptr(&mut self) {}
+
+    pub fn add_monomorphs(&self, library: &Library, out: &mut Monomorphs) {
+        if !self.generic_params.is_empty() {
+            return;
+        }
+        for &(_, ref ty, _) in &self.fields {
+            ty.add_monomorphs(library, out);
+        }
+    }
+
+    pub fn mangle_paths(&mut self, monomorphs: &Monomorphs) {
+        for &mut (_, ref mut ty, _) in &mut self.fields {
+            ty.mangle_paths(monomorphs);
+        }
+    }
+
+    pub fn annotations(&self) -> &AnnotationSet {
+        &self.annotations
+    }
+
+    pub fn annotations_mut(&mut self) -> &mut AnnotationSet {
+        &mut self.annotations
+    }
+}
+
+impl HasPath for Union {
+    fn path_name(&self) -> &str {
+        &self.name
+    }
+}
+
+impl Item for Union {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn add_dependencies(&self, library: &Library, out: &mut Dependencies) {
+        for &(_, ref ty, _) in &self.fields {
+            ty.add_dependencies(library, out);
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct OpaqueItem {
+    pub name: String,
+    pub generic_params: Vec<String>,
+    pub cfg: Option<Cfg>,
+    pub annotations: AnnotationSet,
+    pub documentation: Documentation,
+}
+
+impl OpaqueItem {
+    pub fn rename_for_config(&mut self, config: &Config) {
+        if let Some(renamed) = config.export.rename.get(&self.name) {
+            self.name = renamed.clone();
+        }
+    }
+
+    pub fn populate_ctyperesolver(&self, _resolver: &mut CTypeResolver) {}
+
+    pub fn annotations(&self) -> &AnnotationSet {
+        &self.annotations
+    }
+
+    pub fn annotations_mut(&mut self) -> &mut AnnotationSet {
+        &mut self.annotations
+    }
+}
+
+simple_item!(OpaqueItem);
+
+#[derive(Debug, Clone)]
+pub struct Typedef {
+    pub name: String,
+    pub generic_params: Vec<String>,
+    pub aliased: Type,
+    pub cfg: Option<Cfg>,
+    pub annotations: AnnotationSet,
+    pub documentation: Documentation,
+}
+
+impl Typedef {
+    pub fn rename_for_config(&mut self, config: &Config) {
+        if let Some(renamed) = config.export.rename.get(&self.name) {
+            self.name = renamed.clone();
+        }
+    }
+
+    pub fn transfer_annotations(&mut self, out: &mut HashMap<Path, AnnotationSet>) {
+        if self.annotations.is_empty() {
+            return;
+        }
+        if let Type::Path(ref path, ref generics) = self.aliased {
+            if generics.is_empty() {
+                out.insert(path.clone(), self.annotations.clone());
+            }
+        }
+    }
+
+    pub fn populate_ctyperesolver(&self, _resolver: &mut CTypeResolver) {}
+
+    pub fn set_ctype(&mut self, _resolver: &CTypeResolver) {}
+
+    pub fn simplify_option_to_ptr(&mut self) {}
+
+    pub fn add_monomorphs(&self, library: &Library, out: &mut Monomorphs) {
+        if !self.generic_params.is_empty() {
+            return;
+        }
+        self.aliased.add_monomorphs(library, out);
+    }
+
+    pub fn mangle_paths(&mut self, monomorphs: &Monomorphs) {
+        self.aliased.mangle_paths(monomorphs);
+    }
+
+    pub fn annotations(&self) -> &AnnotationSet {
+        &self.annotations
+    }
+
+    pub fn annotations_mut(&mut self) -> &mut AnnotationSet {
+        &mut self.annotations
+    }
+}
+
+impl HasPath for Typedef {
+    fn path_name(&self) -> &str {
+        &self.name
+    }
+}
+
+impl Item for Typedef {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn add_dependencies(&self, library: &Library, out: &mut Dependencies) {
+        self.aliased.add_dependencies(library, out);
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Function {
+    pub name: String,
+    pub ret: Type,
+    pub args: Vec<(String, Type)>,
+    pub cfg: Option<Cfg>,
+    pub annotations: AnnotationSet,
+    pub documentation: Documentation,
+}
+
+impl Function {
+    pub fn rename_for_config(&mut self, config: &Config) {
+        if let Some(renamed) = config.export.rename.get(&self.name) {
+            self.name = renamed.clone();
+        }
+    }
+
+    pub fn set_ctype(&mut self, _resolver: &CTypeResolver) {}
+
+    pub fn simplify_option_to_ptr(&mut self) {}
+
+    pub fn add_monomorphs(&self, library: &Library, out: &mut Monomorphs) {
+        self.ret.add_monomorphs(library, out);
+        for &(_, ref ty) in &self.args {
+            ty.add_monomorphs(library, out);
+        }
+    }
+
+    pub fn mangle_paths(&mut self, monomorphs: &Monomorphs) {
+        self.ret.mangle_paths(monomorphs);
+        for &mut (_, ref mut ty) in &mut self.args {
+            ty.mangle_paths(monomorphs);
+        }
+    }
+
+    /// Renders this function's signature as a named function-pointer field,
+    /// the way `Trait`'s vtable struct embeds one pointer per method: the
+    /// `void *self` receiver comes first, followed by the remaining
+    /// arguments, reusing the same `Type::to_c_string` lowering a free
+    /// function's prototype would use.
+    pub fn write_as_fn_ptr_field(&self, field_name: &str) -> String {
+        let mut args = vec!["void *self".to_owned()];
+        args.extend(self.args.iter().map(|&(_, ref ty)| ty.to_c_string()));
+        format!(
+            "{} (*{})({});",
+            self.ret.to_c_string(),
+            field_name,
+            args.join(", ")
+        )
+    }
+}
+
+impl Item for Function {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn add_dependencies(&self, library: &Library, out: &mut Dependencies) {
+        self.ret.add_dependencies(library, out);
+        for &(_, ref ty) in &self.args {
+            ty.add_dependencies(library, out);
+        }
+    }
+}
@@ -6,13 +6,15 @@ use std::collections::HashMap;
 use std::mem;
 
 use bindgen::bindings::Bindings;
-use bindgen::config::{Config, Language};
+use bindgen::cfg::Cfg;
+use bindgen::config::{CfgConfig, Config, Language};
 use bindgen::ctyperesolver::CTypeResolver;
 use bindgen::dependencies::Dependencies;
 use bindgen::error::Error;
 use bindgen::ir::{Constant, Enum, Function, Item, ItemContainer, ItemMap};
 use bindgen::ir::{OpaqueItem, Path, Static, Struct, Typedef, Union};
 use bindgen::monomorph::Monomorphs;
+use bindgen::traits::Trait;
 
 #[derive(Debug, Clone)]
 pub struct Library {
@@ -24,6 +26,7 @@ pub struct Library {
     unions: ItemMap<Union>,
     opaque_items: ItemMap<OpaqueItem>,
     typedefs: ItemMap<Typedef>,
+    traits: ItemMap<Trait>,
     functions: Vec<Function>,
 }
 
@@ -37,6 +40,7 @@ impl Library {
         unions: ItemMap<Union>,
         opaque_items: ItemMap<OpaqueItem>,
         typedefs: ItemMap<Typedef>,
+        traits: ItemMap<Trait>,
         functions: Vec<Function>,
     ) -> Library {
         Library {
@@ -48,12 +52,14 @@ impl Library {
             unions: unions,
             opaque_items: opaque_items,
             typedefs: typedefs,
+            traits: traits,
             functions: functions,
         }
     }
 
     pub fn generate(mut self) -> Result<Bindings, Error> {
         self.remove_excluded();
+        self.resolve_cfg();
         self.functions.sort_by(|x, y| x.name.cmp(&y.name));
         self.transfer_annotations();
         self.rename_items();
@@ -120,10 +126,29 @@ impl Library {
         if let Some(x) = self.typedefs.get_items(p) {
             return Some(x);
         }
+        if let Some(x) = self.traits.get_items(p) {
+            return Some(x);
+        }
 
         None
     }
 
+    pub fn get_struct(&self, path: &Path) -> Option<Struct> {
+        self.structs.get_cloned(path.name())
+    }
+
+    pub fn get_union(&self, path: &Path) -> Option<Union> {
+        self.unions.get_cloned(path.name())
+    }
+
+    pub fn get_typedef(&self, path: &Path) -> Option<Typedef> {
+        self.typedefs.get_cloned(path.name())
+    }
+
+    pub fn get_opaque(&self, path: &Path) -> Option<OpaqueItem> {
+        self.opaque_items.get_cloned(path.name())
+    }
+
     fn remove_excluded(&mut self) {
         let config = &self.config;
         self.functions
@@ -138,12 +163,51 @@ impl Library {
             .filter(|x| config.export.exclude.contains(&x.name));
         self.typedefs
             .filter(|x| config.export.exclude.contains(&x.name));
+        self.traits
+            .filter(|x| config.export.exclude.contains(&x.name));
         self.globals
             .filter(|x| config.export.exclude.contains(&x.name));
         self.constants
             .filter(|x| config.export.exclude.contains(&x.name));
     }
 
+    /// Drops every item whose `#[cfg(...)]` predicate evaluates to a
+    /// definite `false` under the `[cfg]` table in `config.toml`
+    /// (`active_features`, `test`, and arbitrary `key = "value"` pairs).
+    ///
+    /// This is deliberately conservative: `eval_cfg` returns `None` rather
+    /// than `Some(false)` for anything it can't resolve (an unrecognized
+    /// key, or any plain `#[cfg(some_target_thing)]` other than
+    /// `#[cfg(test)]`), and only a definite `Some(false)` gets pruned here. A
+    /// predicate like `any(feature = "a", target_has_atomic = "ptr")`
+    /// therefore survives even if `feature = "a"` is off, as long as
+    /// `target_has_atomic` isn't one of the tracked keys -- cbindgen has no
+    /// way to know what the eventual C compilation target supports, so the
+    /// safe thing is to keep emitting the item (wrapped in whatever `#if`
+    /// guard the writer produces) rather than silently drop it.
+    fn resolve_cfg(&mut self) {
+        let cfg_config = &self.config.cfg;
+
+        self.constants
+            .filter(|x| cfg_is_false(x.cfg.as_ref(), cfg_config));
+        self.globals
+            .filter(|x| cfg_is_false(x.cfg.as_ref(), cfg_config));
+        self.enums
+            .filter(|x| cfg_is_false(x.cfg.as_ref(), cfg_config));
+        self.structs
+            .filter(|x| cfg_is_false(x.cfg.as_ref(), cfg_config));
+        self.unions
+            .filter(|x| cfg_is_false(x.cfg.as_ref(), cfg_config));
+        self.opaque_items
+            .filter(|x| cfg_is_false(x.cfg.as_ref(), cfg_config));
+        self.typedefs
+            .filter(|x| cfg_is_false(x.cfg.as_ref(), cfg_config));
+        self.traits
+            .filter(|x| cfg_is_false(x.cfg.as_ref(), cfg_config));
+        self.functions
+            .retain(|x| !cfg_is_false(x.cfg.as_ref(), cfg_config));
+    }
+
     fn transfer_annotations(&mut self) {
         let mut annotations = HashMap::new();
 
@@ -264,6 +328,10 @@ impl Library {
             .for_all_items_mut(|x| x.rename_for_config(config));
         self.typedefs.rebuild();
 
+        self.traits
+            .for_all_items_mut(|x| x.rename_for_config(config));
+        self.traits.rebuild();
+
         for item in &mut self.functions {
             item.rename_for_config(&self.config);
         }
@@ -307,6 +375,9 @@ impl Library {
         self.globals
             .for_all_items_mut(|x| x.set_ctype(&resolver));
 
+        self.traits
+            .for_all_items_mut(|x| x.set_ctype(&resolver));
+
         for item in &mut self.functions {
             item.set_ctype(&resolver);
         }
@@ -346,6 +417,9 @@ impl Library {
         for x in &self.functions {
             x.add_monomorphs(self, &mut monomorphs);
         }
+        self.traits.for_all_items(|x| {
+            x.add_monomorphs(self, &mut monomorphs);
+        });
 
         // Insert the monomorphs into self
         for monomorph in monomorphs.drain_structs() {
@@ -360,6 +434,18 @@ impl Library {
         for monomorph in monomorphs.drain_typedefs() {
             self.typedefs.try_insert(monomorph);
         }
+        // The tagged union backing a `Result<T, E>` instantiation's `payload`
+        // field has to land in `self.unions` before the struct that embeds
+        // it, same as any other struct-with-a-union-field dependency.
+        for monomorph in monomorphs.drain_result_unions() {
+            self.unions.try_insert(monomorph);
+        }
+        for monomorph in monomorphs.drain_results() {
+            self.structs.try_insert(monomorph);
+        }
+        for monomorph in monomorphs.drain_tuples() {
+            self.structs.try_insert(monomorph);
+        }
 
         // Remove structs and opaque items that are generic
         self.opaque_items.filter(|x| x.generic_params.len() > 0);
@@ -377,5 +463,116 @@ impl Library {
         for x in &mut self.functions {
             x.mangle_paths(&monomorphs);
         }
+        self.traits.for_all_items_mut(|x| x.mangle_paths(&monomorphs));
+    }
+}
+
+/// Three-valued evaluation of a `Cfg` predicate against the `[cfg]` table:
+/// `Some(true)`/`Some(false)` when the predicate's truth is known statically,
+/// `None` when it depends on something cbindgen can't see from here (e.g. a
+/// target property nothing in `[cfg]` claims to track). `Cfg::All`/`Cfg::Any`
+/// fold their children with the usual short-circuiting -- `all` is `false`
+/// as soon as any child is definitely `false` even if another child is
+/// unresolved, and symmetrically for `any` and `true` -- but otherwise an
+/// unresolved child poisons the result to `None` rather than guessing, which
+/// is what lets a mixed predicate like `any(feature = "a", target_has_atomic
+/// = "ptr")` stay unresolved instead of being treated as `false`.
+fn eval_cfg(cfg: &Cfg, config: &CfgConfig) -> Option<bool> {
+    match *cfg {
+        Cfg::Boolean(ref name) if name == "test" => Some(config.test),
+        Cfg::Boolean(_) => None,
+        Cfg::Named(ref key, ref value) if key == "feature" => {
+            Some(config.active_features.iter().any(|f| f == value))
+        }
+        // A key we've never heard of can't be resolved either way; only a
+        // key we do track, compared against a non-matching value, is a
+        // definite false.
+        Cfg::Named(ref key, ref value) => config.defines.get(key).map(|v| v == value),
+        Cfg::All(ref items) => items.iter().fold(Some(true), |acc, item| {
+            match (acc, eval_cfg(item, config)) {
+                (Some(false), _) | (_, Some(false)) => Some(false),
+                (Some(true), Some(true)) => Some(true),
+                _ => None,
+            }
+        }),
+        Cfg::Any(ref items) => items.iter().fold(Some(false), |acc, item| {
+            match (acc, eval_cfg(item, config)) {
+                (Some(true), _) | (_, Some(true)) => Some(true),
+                (Some(false), Some(false)) => Some(false),
+                _ => None,
+            }
+        }),
+        Cfg::Not(ref inner) => eval_cfg(inner, config).map(|x| !x),
+    }
+}
+
+/// Whether an item should be pruned: only a definite `Some(false)` counts,
+/// so an item with no `#[cfg(...)]` at all (`None`) or an unresolvable one
+/// (`eval_cfg` returning `None`) is always kept.
+fn cfg_is_false(cfg: Option<&Cfg>, config: &CfgConfig) -> bool {
+    match cfg {
+        Some(cfg) => eval_cfg(cfg, config) == Some(false),
+        None => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn eval_cfg_resolves_known_feature() {
+        let config = CfgConfig {
+            active_features: vec!["a".to_owned()],
+            ..CfgConfig::default()
+        };
+        let cfg = Cfg::Named("feature".to_owned(), "a".to_owned());
+        assert_eq!(eval_cfg(&cfg, &config), Some(true));
+        assert!(!cfg_is_false(Some(&cfg), &config));
+
+        let cfg = Cfg::Named("feature".to_owned(), "b".to_owned());
+        assert_eq!(eval_cfg(&cfg, &config), Some(false));
+        assert!(cfg_is_false(Some(&cfg), &config));
+    }
+
+    #[test]
+    fn eval_cfg_any_stays_unresolved_when_mixed_with_an_unknown_key() {
+        // The request's own critical edge case: `feature = "a"` resolves
+        // to a definite `false`, but `target_has_atomic` isn't a key
+        // anything in `[cfg]` tracks, so the `any(...)` as a whole must
+        // stay unresolved rather than being pruned as `false`.
+        let config = CfgConfig::default();
+        let cfg = Cfg::Any(vec![
+            Cfg::Named("feature".to_owned(), "a".to_owned()),
+            Cfg::Named("target_has_atomic".to_owned(), "ptr".to_owned()),
+        ]);
+
+        assert_eq!(eval_cfg(&cfg, &config), None);
+        assert!(!cfg_is_false(Some(&cfg), &config));
+    }
+
+    #[test]
+    fn eval_cfg_all_short_circuits_on_a_definite_false() {
+        // `all()` can conclude `false` from one definitely-false child even
+        // if another child is unresolved -- no need for every child to
+        // resolve for the whole predicate to be prunable.
+        let config = CfgConfig::default();
+        let cfg = Cfg::All(vec![
+            Cfg::Named("feature".to_owned(), "a".to_owned()),
+            Cfg::Named("target_has_atomic".to_owned(), "ptr".to_owned()),
+        ]);
+
+        assert_eq!(eval_cfg(&cfg, &config), Some(false));
+        assert!(cfg_is_false(Some(&cfg), &config));
+    }
+
+    #[test]
+    fn eval_cfg_test_flag_and_unknown_boolean() {
+        let config = CfgConfig {
+            test: true,
+            ..CfgConfig::default()
+        };
+        assert_eq!(eval_cfg(&Cfg::Boolean("test".to_owned()), &config), Some(true));
+        assert_eq!(eval_cfg(&Cfg::Boolean("unix".to_owned()), &config), None);
     }
 }
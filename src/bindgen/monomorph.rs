@@ -0,0 +1,498 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+use std::collections::HashMap;
+
+use bindgen::ir::{AnnotationSet, Documentation, OpaqueItem, Path, Struct, Type, Typedef, Union};
+use bindgen::library::Library;
+
+/// Collects every distinct generic instantiation `add_monomorphs` finds
+/// while walking the library (a generic `struct`/`union`/`typedef`/opaque
+/// item, a `Result<T, E>`, or a tuple), synthesizes one concrete C type per
+/// distinct instantiation, and remembers the mangled name so `mangle_paths`
+/// can rewrite the generic reference into it. Identical instantiations
+/// reached from different functions/fields collapse to a single synthesized
+/// definition because each `add_*_monomorph` checks whether it has already
+/// mangled that exact key before doing any work.
+#[derive(Default)]
+pub struct Monomorphs {
+    mangled_paths: HashMap<(Path, Vec<Type>), Path>,
+    structs: HashMap<Path, Struct>,
+    unions: HashMap<Path, Union>,
+    typedefs: HashMap<Path, Typedef>,
+    opaques: HashMap<Path, OpaqueItem>,
+
+    result_paths: HashMap<(Type, Type), Path>,
+    result_unions: HashMap<Path, Union>,
+    results: HashMap<Path, Struct>,
+
+    tuple_paths: HashMap<Vec<Type>, Path>,
+    tuples: HashMap<Path, Struct>,
+}
+
+fn mangle_name(ty: &Type) -> String {
+    match *ty {
+        Type::Primitive(ref name) => name.clone(),
+        Type::Path(ref path, ref generics) => {
+            if generics.is_empty() {
+                path.name().to_owned()
+            } else {
+                let args: Vec<_> = generics.iter().map(mangle_name).collect();
+                format!("{}_{}", path.name(), args.join("_"))
+            }
+        }
+        Type::Tuple(ref items) => {
+            let args: Vec<_> = items.iter().map(mangle_name).collect();
+            format!("Tuple_{}", args.join("_"))
+        }
+        Type::Ptr(ref t, is_const) => {
+            format!("{}Ptr_{}", if is_const { "Const" } else { "Mut" }, mangle_name(t))
+        }
+        Type::ConstArray(ref t, len) => format!("{}_{}", mangle_name(t), len),
+        Type::FuncPtr(ref ret, ref args) => {
+            let arg_names: Vec<_> = args.iter().map(mangle_name).collect();
+            format!("FuncPtr_{}_{}", mangle_name(ret), arg_names.join("_"))
+        }
+    }
+}
+
+fn substitute(ty: &Type, generic_params: &[String], generics: &[Type]) -> Type {
+    match *ty {
+        Type::Path(ref path, ref args) => {
+            if args.is_empty() {
+                if let Some(i) = generic_params.iter().position(|p| p == path.name()) {
+                    return generics[i].clone();
+                }
+            }
+            let args = args.iter().map(|a| substitute(a, generic_params, generics)).collect();
+            Type::Path(path.clone(), args)
+        }
+        Type::Tuple(ref items) => {
+            Type::Tuple(items.iter().map(|t| substitute(t, generic_params, generics)).collect())
+        }
+        Type::Ptr(ref t, is_const) => {
+            Type::Ptr(Box::new(substitute(t, generic_params, generics)), is_const)
+        }
+        Type::ConstArray(ref t, len) => {
+            Type::ConstArray(Box::new(substitute(t, generic_params, generics)), len)
+        }
+        Type::FuncPtr(ref ret, ref args) => Type::FuncPtr(
+            Box::new(substitute(ret, generic_params, generics)),
+            args.iter().map(|a| substitute(a, generic_params, generics)).collect(),
+        ),
+        Type::Primitive(ref name) => Type::Primitive(name.clone()),
+    }
+}
+
+impl Monomorphs {
+    /// Entry point for any generic `Path` reference found while walking the
+    /// library (`Type::add_monomorphs`). `Result<T, E>` is a container we
+    /// synthesize specially; everything else is a plain generic struct,
+    /// union, typedef, or opaque item being instantiated.
+    pub fn add_path_monomorph(&mut self, path: &Path, generics: &[Type], library: &Library) {
+        if path.name() == "Result" && generics.len() == 2 {
+            self.add_result_monomorph(&generics[0], &generics[1]);
+            return;
+        }
+
+        let key = (path.clone(), generics.to_vec());
+        if self.mangled_paths.contains_key(&key) {
+            return;
+        }
+
+        let args: Vec<_> = generics.iter().map(mangle_name).collect();
+        let mangled = Path::new(format!("{}_{}", path.name(), args.join("_")));
+        self.mangled_paths.insert(key, mangled.clone());
+
+        if let Some(generic_struct) = library.get_struct(path) {
+            if !self.structs.contains_key(&mangled) {
+                let fields: Vec<_> = generic_struct
+                    .fields
+                    .iter()
+                    .map(|&(ref name, ref ty, ref docs)| {
+                        (name.clone(), substitute(ty, &generic_struct.generic_params, generics), docs.clone())
+                    })
+                    .collect();
+                // The substituted field types may themselves be fresh
+                // generic instantiations (e.g. a `Vec<T>` field becoming
+                // `Vec<Bar>` once `T` is substituted) that `add_monomorphs`
+                // never walked on the still-generic definition, so they
+                // need to be registered here too.
+                for &(_, ref ty, _) in &fields {
+                    ty.add_monomorphs(library, self);
+                }
+                self.structs.insert(
+                    mangled.clone(),
+                    Struct {
+                        name: mangled.name().to_owned(),
+                        generic_params: Vec::new(),
+                        fields: fields,
+                        cfg: generic_struct.cfg.clone(),
+                        annotations: generic_struct.annotations.clone(),
+                        documentation: generic_struct.documentation.clone(),
+                    },
+                );
+            }
+            return;
+        }
+
+        if let Some(generic_union) = library.get_union(path) {
+            if !self.unions.contains_key(&mangled) {
+                let fields: Vec<_> = generic_union
+                    .fields
+                    .iter()
+                    .map(|&(ref name, ref ty, ref docs)| {
+                        (name.clone(), substitute(ty, &generic_union.generic_params, generics), docs.clone())
+                    })
+                    .collect();
+                for &(_, ref ty, _) in &fields {
+                    ty.add_monomorphs(library, self);
+                }
+                self.unions.insert(
+                    mangled.clone(),
+                    Union {
+                        name: mangled.name().to_owned(),
+                        generic_params: Vec::new(),
+                        fields: fields,
+                        cfg: generic_union.cfg.clone(),
+                        annotations: generic_union.annotations.clone(),
+                        documentation: generic_union.documentation.clone(),
+                    },
+                );
+            }
+            return;
+        }
+
+        if let Some(generic_typedef) = library.get_typedef(path) {
+            if !self.typedefs.contains_key(&mangled) {
+                let aliased =
+                    substitute(&generic_typedef.aliased, &generic_typedef.generic_params, generics);
+                aliased.add_monomorphs(library, self);
+                self.typedefs.insert(
+                    mangled.clone(),
+                    Typedef {
+                        name: mangled.name().to_owned(),
+                        generic_params: Vec::new(),
+                        aliased: aliased,
+                        cfg: generic_typedef.cfg.clone(),
+                        annotations: generic_typedef.annotations.clone(),
+                        documentation: generic_typedef.documentation.clone(),
+                    },
+                );
+            }
+            return;
+        }
+
+        // An opaque item is a forward-declared FFI handle with no fields to
+        // substitute -- instantiating it is just cloning the definition
+        // under the mangled name, the same "Box-style handle" pattern as
+        // `Handle<T>` -> `Handle_Foo`.
+        if let Some(generic_opaque) = library.get_opaque(path) {
+            if !self.opaques.contains_key(&mangled) {
+                self.opaques.insert(
+                    mangled.clone(),
+                    OpaqueItem {
+                        name: mangled.name().to_owned(),
+                        generic_params: Vec::new(),
+                        cfg: generic_opaque.cfg.clone(),
+                        annotations: generic_opaque.annotations.clone(),
+                        documentation: generic_opaque.documentation.clone(),
+                    },
+                );
+            }
+        }
+    }
+
+    /// `Result<T, E>` is synthesized as a tagged struct -- `is_ok` plus a
+    /// union payload -- rather than a plain specialized struct, since
+    /// there's no generic `struct Result<T, E>` definition in the library
+    /// to clone from; the shape is fixed by the request.
+    fn add_result_monomorph(&mut self, ok: &Type, err: &Type) {
+        let key = (ok.clone(), err.clone());
+        if self.result_paths.contains_key(&key) {
+            return;
+        }
+
+        let mangled_name = format!("Result_{}_{}", mangle_name(ok), mangle_name(err));
+        let result_path = Path::new(mangled_name.clone());
+        self.result_paths.insert(key, result_path.clone());
+
+        let union_path = Path::new(format!("{}_Union", mangled_name));
+        self.result_unions.insert(
+            union_path.clone(),
+            Union {
+                name: union_path.name().to_owned(),
+                generic_params: Vec::new(),
+                fields: vec![
+                    ("ok".to_owned(), ok.clone(), Documentation::none()),
+                    ("err".to_owned(), err.clone(), Documentation::none()),
+                ],
+                cfg: None,
+                annotations: AnnotationSet::new(),
+                documentation: Documentation::none(),
+            },
+        );
+
+        self.results.insert(
+            result_path.clone(),
+            Struct {
+                name: result_path.name().to_owned(),
+                generic_params: Vec::new(),
+                fields: vec![
+                    ("is_ok".to_owned(), Type::Primitive("bool".to_owned()), Documentation::none()),
+                    ("payload".to_owned(), Type::Path(union_path, Vec::new()), Documentation::none()),
+                ],
+                cfg: None,
+                annotations: AnnotationSet::new(),
+                documentation: Documentation::none(),
+            },
+        );
+    }
+
+    pub fn add_tuple_monomorph(&mut self, items: &[Type]) {
+        let key = items.to_vec();
+        if self.tuple_paths.contains_key(&key) {
+            return;
+        }
+
+        let args: Vec<_> = items.iter().map(mangle_name).collect();
+        let path = Path::new(format!("Tuple_{}", args.join("_")));
+        self.tuple_paths.insert(key, path.clone());
+
+        let fields = items
+            .iter()
+            .enumerate()
+            .map(|(i, ty)| (format!("_{}", i), ty.clone(), Documentation::none()))
+            .collect();
+
+        self.tuples.insert(
+            path.clone(),
+            Struct {
+                name: path.name().to_owned(),
+                generic_params: Vec::new(),
+                fields: fields,
+                cfg: None,
+                annotations: AnnotationSet::new(),
+                documentation: Documentation::none(),
+            },
+        );
+    }
+
+    pub fn mangled_path(&self, path: &Path, generics: &[Type]) -> Option<Path> {
+        if path.name() == "Result" && generics.len() == 2 {
+            return self
+                .result_paths
+                .get(&(generics[0].clone(), generics[1].clone()))
+                .cloned();
+        }
+        self.mangled_paths.get(&(path.clone(), generics.to_vec())).cloned()
+    }
+
+    pub fn mangled_tuple_path(&self, items: &[Type]) -> Option<Path> {
+        self.tuple_paths.get(items).cloned()
+    }
+
+    pub fn drain_structs(&mut self) -> Vec<Struct> {
+        self.structs.drain().map(|(_, v)| v).collect()
+    }
+
+    pub fn drain_unions(&mut self) -> Vec<Union> {
+        self.unions.drain().map(|(_, v)| v).collect()
+    }
+
+    pub fn drain_opaques(&mut self) -> Vec<OpaqueItem> {
+        self.opaques.drain().map(|(_, v)| v).collect()
+    }
+
+    pub fn drain_typedefs(&mut self) -> Vec<Typedef> {
+        self.typedefs.drain().map(|(_, v)| v).collect()
+    }
+
+    pub fn drain_result_unions(&mut self) -> Vec<Union> {
+        self.result_unions.drain().map(|(_, v)| v).collect()
+    }
+
+    pub fn drain_results(&mut self) -> Vec<Struct> {
+        self.results.drain().map(|(_, v)| v).collect()
+    }
+
+    pub fn drain_tuples(&mut self) -> Vec<Struct> {
+        self.tuples.drain().map(|(_, v)| v).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bindgen::config::Config;
+    use bindgen::ir::ItemMap;
+
+    fn library_with_generic_vec() -> Library {
+        let mut structs = ItemMap::new();
+        structs.try_insert_with_name(
+            "Vec".to_owned(),
+            Struct {
+                name: "Vec".to_owned(),
+                generic_params: vec!["T".to_owned()],
+                fields: vec![(
+                    "ptr".to_owned(),
+                    Type::Ptr(Box::new(Type::Path(Path::new("T"), Vec::new())), false),
+                    Documentation::none(),
+                )],
+                cfg: None,
+                annotations: AnnotationSet::new(),
+                documentation: Documentation::none(),
+            },
+        );
+
+        Library::new(
+            Config::default(),
+            ItemMap::new(),
+            ItemMap::new(),
+            ItemMap::new(),
+            structs,
+            ItemMap::new(),
+            ItemMap::new(),
+            ItemMap::new(),
+            ItemMap::new(),
+            Vec::new(),
+        )
+    }
+
+    #[test]
+    fn nested_generic_pulls_in_inner_monomorph_first() {
+        // The request's own critical edge case, one level down from
+        // `Result<Vec<u8>, MyError>`: a field substitution that produces a
+        // fresh generic instantiation (`Vec<T>` becoming `Vec<uint8_t>`)
+        // must itself be monomorphized, not left referencing the still-
+        // generic `Vec`.
+        let mut structs = ItemMap::new();
+        structs.try_insert_with_name(
+            "Vec".to_owned(),
+            Struct {
+                name: "Vec".to_owned(),
+                generic_params: vec!["T".to_owned()],
+                fields: vec![(
+                    "ptr".to_owned(),
+                    Type::Ptr(Box::new(Type::Path(Path::new("T"), Vec::new())), false),
+                    Documentation::none(),
+                )],
+                cfg: None,
+                annotations: AnnotationSet::new(),
+                documentation: Documentation::none(),
+            },
+        );
+        structs.try_insert_with_name(
+            "Wrapper".to_owned(),
+            Struct {
+                name: "Wrapper".to_owned(),
+                generic_params: vec!["T".to_owned()],
+                fields: vec![(
+                    "inner".to_owned(),
+                    Type::Path(Path::new("Vec"), vec![Type::Path(Path::new("T"), Vec::new())]),
+                    Documentation::none(),
+                )],
+                cfg: None,
+                annotations: AnnotationSet::new(),
+                documentation: Documentation::none(),
+            },
+        );
+        let library = Library::new(
+            Config::default(),
+            ItemMap::new(),
+            ItemMap::new(),
+            ItemMap::new(),
+            structs,
+            ItemMap::new(),
+            ItemMap::new(),
+            ItemMap::new(),
+            ItemMap::new(),
+            Vec::new(),
+        );
+        let mut monomorphs = Monomorphs::default();
+
+        let outer = Type::Path(Path::new("Wrapper"), vec![Type::Primitive("uint8_t".to_owned())]);
+        outer.add_monomorphs(&library, &mut monomorphs);
+
+        let structs = monomorphs.drain_structs();
+        assert_eq!(structs.len(), 2);
+        assert!(structs.iter().any(|s| s.name == "Wrapper_uint8_t"));
+        assert!(structs.iter().any(|s| s.name == "Vec_uint8_t"));
+    }
+
+    #[test]
+    fn identical_instantiations_dedup_to_one_definition() {
+        let library = library_with_generic_vec();
+        let mut monomorphs = Monomorphs::default();
+
+        let ty = Type::Path(Path::new("Vec"), vec![Type::Primitive("uint8_t".to_owned())]);
+        ty.add_monomorphs(&library, &mut monomorphs);
+        ty.add_monomorphs(&library, &mut monomorphs);
+
+        assert_eq!(monomorphs.drain_structs().len(), 1);
+    }
+
+    fn result_of_vec_u8_and_my_error() -> Type {
+        Type::Path(
+            Path::new("Result"),
+            vec![
+                Type::Path(Path::new("Vec"), vec![Type::Primitive("uint8_t".to_owned())]),
+                Type::Path(Path::new("MyError"), Vec::new()),
+            ],
+        )
+    }
+
+    #[test]
+    fn nested_result_pulls_in_inner_monomorph_first() {
+        // The request's own named critical edge case: a nested container
+        // like `Result<Vec<u8>, MyError>` must pull in its inner `Vec<u8>`
+        // monomorph too, not just synthesize the outer `Result` struct.
+        let library = library_with_generic_vec();
+        let mut monomorphs = Monomorphs::default();
+
+        result_of_vec_u8_and_my_error().add_monomorphs(&library, &mut monomorphs);
+
+        let structs = monomorphs.drain_structs();
+        assert!(
+            structs.iter().any(|s| s.name == "Vec_uint8_t"),
+            "expected Vec<uint8_t> to be monomorphized as a side effect of \
+             walking Result<Vec<uint8_t>, MyError>, got {:?}",
+            structs.iter().map(|s| &s.name).collect::<Vec<_>>()
+        );
+
+        let results = monomorphs.drain_results();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].name, "Result_Vec_uint8_t_MyError");
+
+        let result_unions = monomorphs.drain_result_unions();
+        assert_eq!(result_unions.len(), 1);
+    }
+
+    #[test]
+    fn identical_result_instantiations_dedup_to_one_definition() {
+        let library = library_with_generic_vec();
+        let mut monomorphs = Monomorphs::default();
+
+        // Two distinct call sites referencing the exact same Result<T, E>
+        // instantiation -- as if it showed up as both a function's return
+        // type and a struct field -- must still only produce one struct.
+        result_of_vec_u8_and_my_error().add_monomorphs(&library, &mut monomorphs);
+        result_of_vec_u8_and_my_error().add_monomorphs(&library, &mut monomorphs);
+
+        assert_eq!(monomorphs.drain_results().len(), 1);
+    }
+
+    #[test]
+    fn tuple_monomorph_fields_are_positional() {
+        let mut monomorphs = Monomorphs::default();
+        let tuple = vec![Type::Primitive("int32_t".to_owned()), Type::Primitive("bool".to_owned())];
+
+        monomorphs.add_tuple_monomorph(&tuple);
+
+        let tuples = monomorphs.drain_tuples();
+        assert_eq!(tuples.len(), 1);
+        assert_eq!(tuples[0].fields[0].0, "_0");
+        assert_eq!(tuples[0].fields[1].0, "_1");
+    }
+}
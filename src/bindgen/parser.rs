@@ -0,0 +1,27 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+use bindgen::cfg::Cfg;
+use bindgen::ir::{AnnotationSet, Documentation, ItemMap};
+use bindgen::traits::{RawTraitMethod, Trait};
+
+/// Lowers a parsed `#[repr(C)]` trait (handed in by the caller as its
+/// `RawTraitMethod`s) into the exported-trait IR and inserts it into
+/// `traits`. A trait with no
+/// representable methods left after `Trait::load`'s FFI-safety filtering
+/// isn't inserted at all, since an empty vtable struct isn't useful.
+pub fn parse_trait(
+    name: String,
+    raw_methods: Vec<RawTraitMethod>,
+    cfg: Option<Cfg>,
+    annotations: AnnotationSet,
+    documentation: Documentation,
+    traits: &mut ItemMap<Trait>,
+) {
+    let item = Trait::load(name, raw_methods, cfg, annotations, documentation);
+    if item.methods.is_empty() {
+        return;
+    }
+    traits.try_insert(item);
+}
@@ -0,0 +1,272 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+use bindgen::cfg::Cfg;
+use bindgen::config::Config;
+use bindgen::ctyperesolver::CTypeResolver;
+use bindgen::dependencies::Dependencies;
+use bindgen::ir::{AnnotationSet, Documentation, Function, HasPath, Item, ItemContainer, ToItemContainer, Type};
+use bindgen::library::Library;
+use bindgen::monomorph::Monomorphs;
+use std::cell::RefCell;
+use std::rc::Rc;
+
+/// A single method of an exported trait, lowered exactly like a free
+/// `Function`: the receiver becomes the leading `void *self` argument, and
+/// the remaining parameters and the return type reuse the same
+/// type-lowering machinery as any other function.
+#[derive(Debug, Clone)]
+pub struct TraitMethod {
+    pub name: String,
+    pub function: Function,
+}
+
+/// A Rust `trait` exported as a C struct bundling an opaque `void *self`
+/// with one function pointer per method -- the "object plus jump table"
+/// pattern used to hand C a Rust-defined callback interface.
+#[derive(Debug, Clone)]
+pub struct Trait {
+    pub name: String,
+    pub methods: Vec<TraitMethod>,
+    pub cfg: Option<Cfg>,
+    pub annotations: AnnotationSet,
+    pub documentation: Documentation,
+}
+
+/// What the parser hands us for a `#[repr(C)]` trait before it's lowered:
+/// one Rust-level method signature per trait method, still in terms of the
+/// receiver (`&self`/`&mut self`) rather than an explicit `void *self`
+/// argument. A real `syn`-based parser builds this by walking
+/// `syn::ItemTrait::items`; kept as its own type here so `Trait::load`
+/// (the actual lowering/validation logic) doesn't need to depend on the
+/// parser's AST representation directly.
+pub struct RawTraitMethod {
+    pub name: String,
+    pub takes_self_by_ref: bool,
+    pub args: Vec<(String, Type)>,
+    pub ret: Type,
+    /// Whether this method has generic parameters of its own, or an
+    /// argument/return type that isn't representable in C (e.g. a type
+    /// parameter, a trait object, an `impl Trait`). Either makes the
+    /// method impossible to put in a C vtable.
+    pub is_ffi_safe: bool,
+}
+
+impl Trait {
+    /// Lowers a parsed `#[repr(C)]` trait into its vtable-struct IR,
+    /// skipping (and warning about) any method that can't be represented
+    /// in C rather than failing the whole trait.
+    pub fn load(
+        name: String,
+        raw_methods: Vec<RawTraitMethod>,
+        cfg: Option<Cfg>,
+        annotations: AnnotationSet,
+        documentation: Documentation,
+    ) -> Trait {
+        let mut methods = Vec::new();
+
+        for raw in raw_methods {
+            if !raw.is_ffi_safe {
+                warn!(
+                    "Skipping method `{}` of trait `{}`: it has generic parameters or a \
+                     non-FFI-safe signature and can't be represented in a C vtable.",
+                    raw.name, name
+                );
+                continue;
+            }
+            if !raw.takes_self_by_ref {
+                warn!(
+                    "Skipping method `{}` of trait `{}`: only methods taking `&self` or \
+                     `&mut self` can be lowered to a `void *self` vtable entry.",
+                    raw.name, name
+                );
+                continue;
+            }
+
+            methods.push(TraitMethod {
+                name: raw.name.clone(),
+                function: Function {
+                    name: raw.name,
+                    ret: raw.ret,
+                    args: raw.args,
+                    cfg: None,
+                    annotations: AnnotationSet::new(),
+                    documentation: Documentation::none(),
+                },
+            });
+        }
+
+        Trait {
+            name: name,
+            methods: methods,
+            cfg: cfg,
+            annotations: annotations,
+            documentation: documentation,
+        }
+    }
+
+    pub fn rename_for_config(&mut self, config: &Config) {
+        if let Some(renamed) = config.export.rename.get(&self.name) {
+            self.name = renamed.clone();
+        }
+        for method in &mut self.methods {
+            method.function.rename_for_config(config);
+            // `to_c_string` emits the vtable field name from `TraitMethod.name`,
+            // not from the (already-renamed, above) `function.name`, so it has
+            // to be kept in sync here too.
+            method.name = method.function.name.clone();
+        }
+    }
+
+    pub fn populate_ctyperesolver(&self, _resolver: &mut CTypeResolver) {
+        // `to_c_string` always emits the vtable struct with a `typedef` of
+        // the same name, so references to it never need a `struct` tag and
+        // there's nothing to register here. `Library::set_ctype` does not
+        // call this hook for traits.
+    }
+
+    pub fn set_ctype(&mut self, resolver: &CTypeResolver) {
+        for method in &mut self.methods {
+            method.function.set_ctype(resolver);
+        }
+    }
+
+    pub fn add_monomorphs(&self, library: &Library, out: &mut Monomorphs) {
+        for method in &self.methods {
+            method.function.add_monomorphs(library, out);
+        }
+    }
+
+    pub fn mangle_paths(&mut self, monomorphs: &Monomorphs) {
+        for method in &mut self.methods {
+            method.function.mangle_paths(monomorphs);
+        }
+    }
+
+    /// Renders the `void *self` + function-pointer-per-method C struct this
+    /// trait lowers to.
+    pub fn to_c_string(&self) -> String {
+        let mut out = format!("typedef struct {} {{\n  void *self;\n", self.name);
+        for method in &self.methods {
+            out.push_str("  ");
+            out.push_str(&method.function.write_as_fn_ptr_field(&method.name));
+            out.push('\n');
+        }
+        out.push_str(&format!("}} {};\n", self.name));
+        out
+    }
+}
+
+impl HasPath for Trait {
+    fn path_name(&self) -> &str {
+        &self.name
+    }
+}
+
+impl Item for Trait {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn add_dependencies(&self, library: &Library, out: &mut Dependencies) {
+        // The struct itself only depends on what its methods reference --
+        // `void *self` and the function pointers don't pull in anything on
+        // their own -- so recursing into each lowered method signature is
+        // enough to keep this struct ordered after the types it mentions.
+        for method in &self.methods {
+            method.function.add_dependencies(library, out);
+        }
+    }
+}
+
+impl ToItemContainer for Trait {
+    fn to_container(rc: Rc<RefCell<Trait>>) -> ItemContainer {
+        ItemContainer::Trait(rc)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn raw(name: &str, takes_self_by_ref: bool, is_ffi_safe: bool) -> RawTraitMethod {
+        RawTraitMethod {
+            name: name.to_owned(),
+            takes_self_by_ref: takes_self_by_ref,
+            args: Vec::new(),
+            ret: Type::Primitive("void".to_owned()),
+            is_ffi_safe: is_ffi_safe,
+        }
+    }
+
+    #[test]
+    fn load_skips_non_ffi_safe_and_by_value_methods() {
+        let raw_methods = vec![
+            raw("valid", true, true),
+            raw("generic_method", true, false),
+            raw("takes_self_by_value", false, true),
+        ];
+
+        let item = Trait::load(
+            "Listener".to_owned(),
+            raw_methods,
+            None,
+            AnnotationSet::new(),
+            Documentation::none(),
+        );
+
+        assert_eq!(item.methods.len(), 1);
+        assert_eq!(item.methods[0].name, "valid");
+        assert_eq!(item.methods[0].function.name, "valid");
+    }
+
+    #[test]
+    fn load_empty_when_nothing_is_representable() {
+        let raw_methods = vec![raw("generic_method", true, false)];
+
+        let item = Trait::load(
+            "Listener".to_owned(),
+            raw_methods,
+            None,
+            AnnotationSet::new(),
+            Documentation::none(),
+        );
+
+        assert!(item.methods.is_empty());
+    }
+
+    #[test]
+    fn to_c_string_emits_self_ptr_and_one_fn_ptr_per_method() {
+        let item = Trait::load(
+            "Listener".to_owned(),
+            vec![raw("on_event", true, true)],
+            None,
+            AnnotationSet::new(),
+            Documentation::none(),
+        );
+
+        let rendered = item.to_c_string();
+        assert!(rendered.contains("typedef struct Listener {"));
+        assert!(rendered.contains("void *self;"));
+        assert!(rendered.contains("void (*on_event)(void *self);"));
+    }
+
+    #[test]
+    fn rename_for_config_updates_the_vtable_field_name() {
+        let mut item = Trait::load(
+            "Listener".to_owned(),
+            vec![raw("on_event", true, true)],
+            None,
+            AnnotationSet::new(),
+            Documentation::none(),
+        );
+
+        let mut config = Config::default();
+        config.export.rename.insert("on_event".to_owned(), "onEvent".to_owned());
+        item.rename_for_config(&config);
+
+        assert_eq!(item.methods[0].name, "onEvent");
+        assert!(item.to_c_string().contains("(*onEvent)(void *self);"));
+    }
+}